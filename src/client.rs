@@ -0,0 +1,305 @@
+//! A small client for talking to this crate's server over the text
+//! protocol, reusing the same `Resp`/`Value` types the server serializes.
+//! `SyncConnection` blocks on `std::net::TcpStream`; `AsyncConnection` is
+//! the tokio equivalent. Both expose a `noreply` fire-and-forget path for
+//! pipelining writes without waiting on an ack per call.
+
+use std::io::{BufRead, BufReader as StdBufReader, Read, Write};
+use std::net::TcpStream;
+
+use anyhow::{Error, Result};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader as TokioBufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream as TokioTcpStream;
+
+use crate::{Resp, Value};
+
+fn storage_command(name: &str, key: &str, flags: u16, exptime: u64, cas_unique: Option<u64>, data: &[u8], no_reply: bool) -> Vec<u8> {
+    let mut line = match cas_unique {
+        Some(cas) => format!("{} {} {} {} {} {}", name, key, flags, exptime, data.len(), cas),
+        None => format!("{} {} {} {} {}", name, key, flags, exptime, data.len()),
+    };
+    if no_reply {
+        line.push_str(" noreply");
+    }
+    line.push_str("\r\n");
+
+    let mut bytes = line.into_bytes();
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(b"\r\n");
+    bytes
+}
+
+fn delete_command(key: &str, no_reply: bool) -> Vec<u8> {
+    let mut line = format!("delete {}", key);
+    if no_reply {
+        line.push_str(" noreply");
+    }
+    line.push_str("\r\n");
+    line.into_bytes()
+}
+
+fn get_command(key: &str) -> Vec<u8> {
+    format!("get {}\r\n", key).into_bytes()
+}
+
+/// Parses a single-line reply (`STORED`, `NOT_STORED`, `DELETED`,
+/// `NOT_FOUND`, `EXISTS`, `TOUCHED`, `OK`, a bare number, or
+/// `CLIENT_ERROR ...`).
+fn parse_simple_reply(line: &str) -> Result<Resp> {
+    let line = line.trim_end_matches('\n').trim_end_matches('\r');
+    match line.split_whitespace().next() {
+        Some("STORED") => Ok(Resp::Stored),
+        Some("NOT_STORED") => Ok(Resp::NotStored),
+        Some("DELETED") => Ok(Resp::Deleted),
+        Some("NOT_FOUND") => Ok(Resp::NotFound),
+        Some("EXISTS") => Ok(Resp::Exists),
+        Some("TOUCHED") => Ok(Resp::Touched),
+        Some("OK") => Ok(Resp::Ok),
+        Some("CLIENT_ERROR") => Ok(Resp::ClientError(line.trim_start_matches("CLIENT_ERROR").trim().to_string())),
+        Some(n) => n.parse::<u64>().map(Resp::Number).map_err(|_| Error::msg(format!("Unexpected reply {}", line))),
+        None => Err(Error::msg("Empty reply")),
+    }
+}
+
+/// Blocking client API: each call sends a request and waits for the
+/// server's reply before returning.
+pub trait SyncClient {
+    fn set(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp>;
+    fn add(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp>;
+    fn replace(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp>;
+    fn get(&mut self, key: &str) -> Result<Option<Value>>;
+    fn delete(&mut self, key: &str) -> Result<Resp>;
+    fn cas(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8], cas_unique: u64) -> Result<Resp>;
+    /// Sends `set` with `noreply` and returns without waiting on an ack, so
+    /// callers can pipeline many writes back to back.
+    fn set_no_reply(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<()>;
+}
+
+/// Owns a blocking connection's read and write halves (a cloned
+/// `TcpStream` for each, mirroring the server's split read/write tasks).
+pub struct SyncConnection {
+    reader: StdBufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl SyncConnection {
+    pub fn connect(addr: &str) -> Result<SyncConnection> {
+        let stream = TcpStream::connect(addr)?;
+        let writer = stream.try_clone()?;
+        Ok(SyncConnection {
+            reader: StdBufReader::new(stream),
+            writer,
+        })
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let n = self.reader.read_line(&mut line)?;
+        if n == 0 {
+            return Err(Error::msg("Connection closed"));
+        }
+        Ok(line)
+    }
+
+    fn read_value(&mut self) -> Result<Option<Value>> {
+        let header = self.read_line()?;
+        let header = header.trim_end_matches('\n').trim_end_matches('\r');
+        if header == "END" {
+            return Ok(None);
+        }
+
+        let parts = header.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 4 || parts[0] != "VALUE" {
+            return Err(Error::msg(format!("Unexpected reply {}", header)));
+        }
+        let name = parts[1].to_string();
+        let flags = parts[2].parse::<u16>()?;
+        let byte_count = parts[3].parse::<usize>()?;
+
+        let mut data_buf = vec![0u8; byte_count + 2];
+        self.reader.read_exact(&mut data_buf)?;
+        let data_block = data_buf[..byte_count].to_vec();
+
+        let end = self.read_line()?;
+        if end.trim_end_matches('\n').trim_end_matches('\r') != "END" {
+            return Err(Error::msg("Expected END after VALUE"));
+        }
+
+        Ok(Some(Value {
+            name,
+            flags,
+            byte_count,
+            data_block,
+        }))
+    }
+}
+
+impl SyncClient for SyncConnection {
+    fn set(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp> {
+        self.writer.write_all(&storage_command("set", key, flags, exptime, None, data, false))?;
+        parse_simple_reply(&self.read_line()?)
+    }
+
+    fn add(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp> {
+        self.writer.write_all(&storage_command("add", key, flags, exptime, None, data, false))?;
+        parse_simple_reply(&self.read_line()?)
+    }
+
+    fn replace(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp> {
+        self.writer.write_all(&storage_command("replace", key, flags, exptime, None, data, false))?;
+        parse_simple_reply(&self.read_line()?)
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<Value>> {
+        self.writer.write_all(&get_command(key))?;
+        self.read_value()
+    }
+
+    fn delete(&mut self, key: &str) -> Result<Resp> {
+        self.writer.write_all(&delete_command(key, false))?;
+        parse_simple_reply(&self.read_line()?)
+    }
+
+    fn cas(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8], cas_unique: u64) -> Result<Resp> {
+        self.writer.write_all(&storage_command("cas", key, flags, exptime, Some(cas_unique), data, false))?;
+        parse_simple_reply(&self.read_line()?)
+    }
+
+    fn set_no_reply(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<()> {
+        self.writer.write_all(&storage_command("set", key, flags, exptime, None, data, true))?;
+        Ok(())
+    }
+}
+
+/// Async client API: each method is an `async fn` driving the request over
+/// a tokio connection rather than blocking the current thread.
+#[allow(async_fn_in_trait)]
+pub trait AsyncClient {
+    async fn set(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp>;
+    async fn add(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp>;
+    async fn replace(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp>;
+    async fn get(&mut self, key: &str) -> Result<Option<Value>>;
+    async fn delete(&mut self, key: &str) -> Result<Resp>;
+    async fn cas(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8], cas_unique: u64) -> Result<Resp>;
+    /// Sends `set` with `noreply` and returns without waiting on an ack, so
+    /// callers can pipeline many writes back to back.
+    async fn set_no_reply(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<()>;
+}
+
+/// Owns a tokio connection's split read/write halves, the same split used
+/// server-side via `TcpStream::into_split`.
+pub struct AsyncConnection {
+    reader: TokioBufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl AsyncConnection {
+    pub async fn connect(addr: &str) -> Result<AsyncConnection> {
+        let stream = TokioTcpStream::connect(addr).await?;
+        let (read_half, writer) = stream.into_split();
+        Ok(AsyncConnection {
+            reader: TokioBufReader::new(read_half),
+            writer,
+        })
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let n = self.reader.read_line(&mut line).await?;
+        if n == 0 {
+            return Err(Error::msg("Connection closed"));
+        }
+        Ok(line)
+    }
+
+    async fn read_value(&mut self) -> Result<Option<Value>> {
+        let header = self.read_line().await?;
+        let header = header.trim_end_matches('\n').trim_end_matches('\r');
+        if header == "END" {
+            return Ok(None);
+        }
+
+        let parts = header.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 4 || parts[0] != "VALUE" {
+            return Err(Error::msg(format!("Unexpected reply {}", header)));
+        }
+        let name = parts[1].to_string();
+        let flags = parts[2].parse::<u16>()?;
+        let byte_count = parts[3].parse::<usize>()?;
+
+        let mut data_buf = vec![0u8; byte_count + 2];
+        self.reader.read_exact(&mut data_buf).await?;
+        let data_block = data_buf[..byte_count].to_vec();
+
+        let end = self.read_line().await?;
+        if end.trim_end_matches('\n').trim_end_matches('\r') != "END" {
+            return Err(Error::msg("Expected END after VALUE"));
+        }
+
+        Ok(Some(Value {
+            name,
+            flags,
+            byte_count,
+            data_block,
+        }))
+    }
+}
+
+impl AsyncClient for AsyncConnection {
+    async fn set(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp> {
+        self.writer.write_all(&storage_command("set", key, flags, exptime, None, data, false)).await?;
+        parse_simple_reply(&self.read_line().await?)
+    }
+
+    async fn add(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp> {
+        self.writer.write_all(&storage_command("add", key, flags, exptime, None, data, false)).await?;
+        parse_simple_reply(&self.read_line().await?)
+    }
+
+    async fn replace(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<Resp> {
+        self.writer.write_all(&storage_command("replace", key, flags, exptime, None, data, false)).await?;
+        parse_simple_reply(&self.read_line().await?)
+    }
+
+    async fn get(&mut self, key: &str) -> Result<Option<Value>> {
+        self.writer.write_all(&get_command(key)).await?;
+        self.read_value().await
+    }
+
+    async fn delete(&mut self, key: &str) -> Result<Resp> {
+        self.writer.write_all(&delete_command(key, false)).await?;
+        parse_simple_reply(&self.read_line().await?)
+    }
+
+    async fn cas(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8], cas_unique: u64) -> Result<Resp> {
+        self.writer.write_all(&storage_command("cas", key, flags, exptime, Some(cas_unique), data, false)).await?;
+        parse_simple_reply(&self.read_line().await?)
+    }
+
+    async fn set_no_reply(&mut self, key: &str, flags: u16, exptime: u64, data: &[u8]) -> Result<()> {
+        self.writer.write_all(&storage_command("set", key, flags, exptime, None, data, true)).await?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_storage_command_formats_header_and_trailer() {
+    let bytes = storage_command("set", "hello", 0, 0, None, b"world", false);
+
+    assert_eq!(bytes, b"set hello 0 0 5\r\nworld\r\n".to_vec());
+}
+
+#[test]
+fn test_storage_command_includes_cas_and_noreply() {
+    let bytes = storage_command("cas", "hello", 0, 0, Some(42), b"world", true);
+
+    assert_eq!(bytes, b"cas hello 0 0 5 42 noreply\r\nworld\r\n".to_vec());
+}
+
+#[test]
+fn test_parse_simple_reply() {
+    assert_eq!(parse_simple_reply("STORED\r\n").unwrap(), Resp::Stored);
+    assert_eq!(parse_simple_reply("NOT_FOUND\r\n").unwrap(), Resp::NotFound);
+    assert_eq!(parse_simple_reply("42\r\n").unwrap(), Resp::Number(42));
+}
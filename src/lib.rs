@@ -0,0 +1,1169 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Error, Result};
+use dashmap::DashMap;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+pub mod client;
+
+/// WebSocket frontend, bridging WS messages to [`dispatch`]. Gated behind
+/// the `websocket` feature so the TCP-only build doesn't pull in
+/// `tokio-tungstenite`.
+#[cfg(feature = "websocket")]
+pub mod ws;
+
+#[derive(Debug, PartialEq)]
+pub enum Req {
+    Set(Common),
+    Get(Get),
+    Gets(Gets),
+    Add(Common),
+    Replace(Common),
+    Append(Common),
+    Prepend(Common),
+    Cas(Cas),
+    Delete(Delete),
+    Incr(IncrDecr),
+    Decr(IncrDecr),
+    Touch(Touch),
+    FlushAll(FlushAll),
+    Stats,
+}
+
+/// Largest data block `read_command` will allocate for, matching real
+/// memcached's built-in 1MB item size limit. A client claiming a bigger
+/// `byte_count` gets `FrameError::TooLarge` instead of an unbounded
+/// allocation.
+pub const MAX_ITEM_SIZE: usize = 1024 * 1024;
+
+/// Error reading and framing a single command off the wire.
+///
+/// `BadDataChunk` is surfaced to the client as `CLIENT_ERROR bad data chunk`
+/// rather than dropping the connection, matching real memcached behaviour.
+/// `TooLarge` is surfaced as `SERVER_ERROR object too large for cache`; the
+/// oversized data block is still swallowed off the wire first so the next
+/// command on the connection stays in sync.
+pub enum FrameError {
+    BadDataChunk,
+    TooLarge,
+    Protocol(Error),
+}
+
+impl From<Error> for FrameError {
+    fn from(err: Error) -> Self {
+        FrameError::Protocol(err)
+    }
+}
+
+/// Discards `len` bytes from `reader` in fixed-size chunks, without ever
+/// allocating a buffer anywhere near `len` itself. Used to drain a data
+/// block whose declared size is rejected for being too large, so the
+/// connection stays framed for the next command.
+async fn swallow<R: AsyncBufRead + Unpin>(reader: &mut R, mut len: usize) -> Result<(), Error> {
+    let mut chunk = [0u8; 8192];
+    while len > 0 {
+        let take = len.min(chunk.len());
+        reader.read_exact(&mut chunk[..take]).await?;
+        len -= take;
+    }
+    Ok(())
+}
+
+/// Reads exactly one command from `reader`, handling the trailing data block
+/// for storage commands itself so callers never have to guess how many bytes
+/// a command spans. Returns `Ok(None)` on a clean EOF between commands.
+pub async fn read_command<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Req>, FrameError> {
+    let mut line = Vec::new();
+    let n = reader.read_until(b'\n', &mut line).await.map_err(Error::from)?;
+    if n == 0 {
+        return Ok(None);
+    }
+
+    let line = String::from_utf8_lossy(&line);
+    let header = line.trim_end_matches('\n').trim_end_matches('\r');
+    let name = header
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| Error::msg("Empty command"))?;
+
+    match name {
+        "set" | "add" | "replace" | "append" | "prepend" | "cas" => {
+            let byte_count = header
+                .split_whitespace()
+                .nth(4)
+                .ok_or_else(|| Error::msg(format!("Invalid command {}", header)))?
+                .parse::<usize>()
+                .map_err(Error::from)?;
+
+            if byte_count > MAX_ITEM_SIZE {
+                // Best-effort: drain the declared data so a well-formed
+                // connection stays framed for its next command. If the
+                // client doesn't actually send that much, the connection
+                // was already broken, but we still report the real reason.
+                let _ = swallow(reader, byte_count + 2).await;
+                return Err(FrameError::TooLarge);
+            }
+
+            let mut data_buf = vec![0u8; byte_count + 2];
+            reader.read_exact(&mut data_buf).await.map_err(Error::from)?;
+            if &data_buf[byte_count..] != b"\r\n" {
+                return Err(FrameError::BadDataChunk);
+            }
+            let data_block = data_buf[..byte_count].to_vec();
+
+            if name == "cas" {
+                return Ok(Some(Req::Cas(Cas::from(header, data_block)?)));
+            }
+
+            let common = Common::from(header, data_block)?;
+
+            Ok(Some(match name {
+                "set" => Req::Set(common),
+                "add" => Req::Add(common),
+                "replace" => Req::Replace(common),
+                "append" => Req::Append(common),
+                "prepend" => Req::Prepend(common),
+                _ => unreachable!(),
+            }))
+        }
+        "get" => Ok(Some(Req::Get(Get::from(header)?))),
+        "gets" => Ok(Some(Req::Gets(Gets::from(header)?))),
+        "delete" => Ok(Some(Req::Delete(Delete::from(header)?))),
+        "incr" => Ok(Some(Req::Incr(IncrDecr::from(header)?))),
+        "decr" => Ok(Some(Req::Decr(IncrDecr::from(header)?))),
+        "touch" => Ok(Some(Req::Touch(Touch::from(header)?))),
+        "flush_all" => Ok(Some(Req::FlushAll(FlushAll::from(header)?))),
+        "stats" => Ok(Some(Req::Stats)),
+        _ => Err(Error::msg(format!("Invalid command {}", name)).into()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Common {
+    pub name: String,
+    pub key: String,
+    pub flags: u16,
+    pub exptime: i64,
+    pub byte_count: usize,
+    pub no_reply: bool,
+    pub data_block: Vec<u8>,
+}
+
+impl Common {
+    /// Builds a storage command from its already-framed header line and data
+    /// bytes. `data_block` is the exact payload the caller read via
+    /// `read_exact`, already stripped of its trailing `\r\n`.
+    pub fn from(header: &str, data_block: Vec<u8>) -> Result<Common, Error> {
+        let parts = header.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 5 {
+            return Err(Error::msg(format!("Invalid set format {}", header)));
+        }
+
+        let name = parts[0].to_string();
+        let key = parts[1].to_string();
+        let flags = parts[2].parse::<u16>()?;
+        let exptime = parts[3].parse::<i64>()?;
+        let byte_count = parts[4].parse::<usize>()?;
+        let no_reply = parts.get(5).is_some_and(|p| *p == "noreply");
+
+        Ok(Common {
+            name,
+            key,
+            flags,
+            exptime,
+            byte_count,
+            no_reply,
+            data_block,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Get {
+    pub name: String,
+    pub key: String,
+}
+
+impl Get {
+    pub fn from(data: &str) -> Result<Get, Error> {
+        let parts = data.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 2 {
+            return Err(Error::msg(format!("Invalid get format {}", data)));
+        }
+
+        let name = parts[0].to_string();
+        let key = parts[1].to_string();
+
+        Ok(Get {
+            name,
+            key,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Gets {
+    pub name: String,
+    pub keys: Vec<String>,
+}
+
+impl Gets {
+    pub fn from(data: &str) -> Result<Gets, Error> {
+        let parts = data.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 2 {
+            return Err(Error::msg(format!("Invalid gets format {}", data)));
+        }
+
+        let name = parts[0].to_string();
+        let keys = parts[1..].iter().map(|k| k.to_string()).collect();
+
+        Ok(Gets {
+            name,
+            keys,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cas {
+    pub key: String,
+    pub flags: u16,
+    pub exptime: i64,
+    pub byte_count: usize,
+    pub cas_unique: u64,
+    pub no_reply: bool,
+    pub data_block: Vec<u8>,
+}
+
+impl Cas {
+    pub fn from(header: &str, data_block: Vec<u8>) -> Result<Cas, Error> {
+        let parts = header.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 6 {
+            return Err(Error::msg(format!("Invalid cas format {}", header)));
+        }
+
+        let key = parts[1].to_string();
+        let flags = parts[2].parse::<u16>()?;
+        let exptime = parts[3].parse::<i64>()?;
+        let byte_count = parts[4].parse::<usize>()?;
+        let cas_unique = parts[5].parse::<u64>()?;
+        let no_reply = parts.get(6).is_some_and(|p| *p == "noreply");
+
+        Ok(Cas {
+            key,
+            flags,
+            exptime,
+            byte_count,
+            cas_unique,
+            no_reply,
+            data_block,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Delete {
+    pub name: String,
+    pub key: String,
+    pub no_reply: bool,
+}
+
+impl Delete {
+    pub fn from(data: &str) -> Result<Delete, Error> {
+        let parts = data.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 2 {
+            return Err(Error::msg(format!("Invalid delete format {}", data)));
+        }
+
+        let name = parts[0].to_string();
+        let key = parts[1].to_string();
+        let no_reply = parts.get(2).is_some_and(|p| *p == "noreply");
+
+        Ok(Delete {
+            name,
+            key,
+            no_reply,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct IncrDecr {
+    pub name: String,
+    pub key: String,
+    pub value: u64,
+    pub no_reply: bool,
+}
+
+impl IncrDecr {
+    pub fn from(data: &str) -> Result<IncrDecr, Error> {
+        let parts = data.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 3 {
+            return Err(Error::msg(format!("Invalid incr/decr format {}", data)));
+        }
+
+        let name = parts[0].to_string();
+        let key = parts[1].to_string();
+        let value = parts[2].parse::<u64>()?;
+        let no_reply = parts.get(3).is_some_and(|p| *p == "noreply");
+
+        Ok(IncrDecr {
+            name,
+            key,
+            value,
+            no_reply,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Touch {
+    pub name: String,
+    pub key: String,
+    pub exptime: i64,
+    pub no_reply: bool,
+}
+
+impl Touch {
+    pub fn from(data: &str) -> Result<Touch, Error> {
+        let parts = data.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() < 3 {
+            return Err(Error::msg(format!("Invalid touch format {}", data)));
+        }
+
+        let name = parts[0].to_string();
+        let key = parts[1].to_string();
+        let exptime = parts[2].parse::<i64>()?;
+        let no_reply = parts.get(3).is_some_and(|p| *p == "noreply");
+
+        Ok(Touch {
+            name,
+            key,
+            exptime,
+            no_reply,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FlushAll {
+    pub no_reply: bool,
+}
+
+impl FlushAll {
+    pub fn from(data: &str) -> Result<FlushAll, Error> {
+        let parts = data.split_whitespace().collect::<Vec<&str>>();
+        let no_reply = parts.get(1).is_some_and(|p| *p == "noreply");
+
+        Ok(FlushAll {
+            no_reply,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Resp {
+    Stored,
+    NotStored,
+    End,
+    Value(Value),
+    Gets(Vec<(Value, u64)>),
+    Deleted,
+    NotFound,
+    Exists,
+    Touched,
+    Ok,
+    Number(u64),
+    Stats(Vec<(String, String)>),
+    ClientError(String),
+    ServerError(String),
+}
+
+impl Resp {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match self {
+            Resp::Stored => "STORED\r\n".to_string(),
+            Resp::NotStored => "NOT_STORED\r\n".to_string(),
+            Resp::End => "END\r\n".to_string(),
+            Resp::Value(value) => format!("{}END\r\n", value.to_string(None)),
+            Resp::Gets(values) => {
+                let mut out = String::new();
+                for (value, cas) in values {
+                    out.push_str(&value.to_string(Some(*cas)));
+                }
+                out.push_str("END\r\n");
+                out
+            }
+            Resp::Deleted => "DELETED\r\n".to_string(),
+            Resp::NotFound => "NOT_FOUND\r\n".to_string(),
+            Resp::Exists => "EXISTS\r\n".to_string(),
+            Resp::Touched => "TOUCHED\r\n".to_string(),
+            Resp::Ok => "OK\r\n".to_string(),
+            Resp::Number(n) => format!("{}\r\n", n),
+            Resp::Stats(stats) => {
+                let mut out = String::new();
+                for (name, value) in stats {
+                    out.push_str(&format!("STAT {} {}\r\n", name, value));
+                }
+                out.push_str("END\r\n");
+                out
+            }
+            Resp::ClientError(message) => format!("CLIENT_ERROR {}\r\n", message),
+            Resp::ServerError(message) => format!("SERVER_ERROR {}\r\n", message),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Entry {
+    pub data: Value,
+    pub expires_at: Option<Instant>,
+    pub cas: u64,
+    pub last_accessed: Instant,
+    pub size: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Value {
+    pub name: String,
+    pub flags: u16,
+    pub byte_count: usize,
+    pub data_block: Vec<u8>,
+}
+
+impl Value {
+    pub fn to_string(&self, cas: Option<u64>) -> String {
+        match cas {
+            Some(cas) => format!("VALUE {} {} {} {}\r\n{}\r\n", self.name, self.flags, self.byte_count, cas, String::from_utf8_lossy(&self.data_block)),
+            None => format!("VALUE {} {} {}\r\n{}\r\n", self.name, self.flags, self.byte_count, String::from_utf8_lossy(&self.data_block)),
+        }
+    }
+}
+
+/// Default memory cap, matching real memcached's built-in default of 64MB.
+pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// State shared by every connection: the key/value storage plus the
+/// bookkeeping needed to enforce `max_bytes` with LRU eviction.
+pub struct Shared {
+    pub storage: DashMap<String, Entry>,
+    pub cas_counter: AtomicU64,
+    pub current_bytes: AtomicUsize,
+    pub max_bytes: usize,
+    pub evictions: AtomicU64,
+}
+
+impl Shared {
+    pub fn new(max_bytes: usize) -> Shared {
+        Shared {
+            storage: DashMap::new(),
+            cas_counter: AtomicU64::new(0),
+            current_bytes: AtomicUsize::new(0),
+            max_bytes,
+            evictions: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Reads the memory cap from `--max-bytes <N>` or the `MEMCACHED_MAX_BYTES`
+/// env var, falling back to `DEFAULT_MAX_BYTES`.
+pub fn max_bytes_from_env() -> usize {
+    let args = std::env::args().collect::<Vec<String>>();
+    let from_arg = args
+        .windows(2)
+        .find(|w| w[0] == "--max-bytes")
+        .and_then(|w| w[1].parse::<usize>().ok());
+
+    from_arg
+        .or_else(|| std::env::var("MEMCACHED_MAX_BYTES").ok().and_then(|v| v.parse().ok()))
+        .unwrap_or(DEFAULT_MAX_BYTES)
+}
+
+/// Default interval between background expiration sweeps.
+pub const DEFAULT_REAP_INTERVAL_SECS: u64 = 30;
+
+/// Reads the reaper interval from `--reap-interval-secs <N>` or the
+/// `MEMCACHED_REAP_INTERVAL_SECS` env var, falling back to
+/// `DEFAULT_REAP_INTERVAL_SECS`.
+pub fn reap_interval_from_env() -> Duration {
+    let args = std::env::args().collect::<Vec<String>>();
+    let from_arg = args
+        .windows(2)
+        .find(|w| w[0] == "--reap-interval-secs")
+        .and_then(|w| w[1].parse::<u64>().ok());
+
+    let secs = from_arg
+        .or_else(|| std::env::var("MEMCACHED_REAP_INTERVAL_SECS").ok().and_then(|v| v.parse().ok()))
+        .unwrap_or(DEFAULT_REAP_INTERVAL_SECS);
+
+    Duration::from_secs(secs.max(1))
+}
+
+/// Removes every entry whose `expires_at` has passed. Runs alongside lazy,
+/// access-time expiration so keys that are never read again don't leak.
+pub fn reap_expired(shared: &Shared) -> usize {
+    let now = Instant::now();
+    let expired = shared
+        .storage
+        .iter()
+        .filter(|entry| entry.expires_at.is_some_and(|e| e <= now))
+        .map(|entry| entry.key().clone())
+        .collect::<Vec<String>>();
+
+    let count = expired.len();
+    for key in expired {
+        remove(shared, &key);
+    }
+    count
+}
+
+/// Removes `key`, keeping `current_bytes` in sync. Returns the removed entry.
+pub fn remove(shared: &Shared, key: &str) -> Option<Entry> {
+    let removed = shared.storage.remove(key).map(|(_, entry)| entry);
+    if let Some(entry) = &removed {
+        shared.current_bytes.fetch_sub(entry.size, Ordering::SeqCst);
+    }
+    removed
+}
+
+/// Evicts the single least-recently-used entry. Returns `false` if storage
+/// was already empty.
+pub fn evict_lru(shared: &Shared) -> bool {
+    let lru_key = shared
+        .storage
+        .iter()
+        .min_by_key(|entry| entry.last_accessed)
+        .map(|entry| entry.key().clone());
+
+    match lru_key {
+        Some(key) => {
+            remove(shared, &key);
+            shared.evictions.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Items stored with an exptime beyond this many seconds are interpreted as
+/// absolute Unix timestamps rather than a relative offset, matching real
+/// memcached's `REALTIME_MAXDELTA` handling.
+pub const THIRTY_DAYS_SECS: i64 = 60 * 60 * 24 * 30;
+
+/// Resolves a command's `exptime` to an expiry instant. `0` means store
+/// forever; negative values expire immediately; values past
+/// `THIRTY_DAYS_SECS` are absolute Unix timestamps.
+pub fn expires_at(exptime: i64) -> Option<Instant> {
+    if exptime == 0 {
+        return None;
+    }
+
+    if exptime < 0 {
+        return Some(Instant::now());
+    }
+
+    if exptime <= THIRTY_DAYS_SECS {
+        return Some(Instant::now() + Duration::from_secs(exptime as u64));
+    }
+
+    let now_unix = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+    if exptime <= now_unix {
+        Some(Instant::now())
+    } else {
+        Some(Instant::now() + Duration::from_secs((exptime - now_unix) as u64))
+    }
+}
+
+pub fn store(shared: &Shared, common: Common) -> Option<Resp> {
+    let size = common.key.len() + common.data_block.len();
+    let old_size = shared.storage.get(&common.key).map(|entry| entry.size).unwrap_or(0);
+
+    while shared.current_bytes.load(Ordering::SeqCst) + size > shared.max_bytes + old_size {
+        if !evict_lru(shared) {
+            break;
+        }
+    }
+
+    let value = Value {
+        name: common.key.clone(),
+        data_block: common.data_block.clone(),
+        flags: common.flags,
+        byte_count: common.byte_count,
+    };
+    let entry = Entry {
+        data: value.clone(),
+        expires_at: expires_at(common.exptime),
+        cas: shared.cas_counter.fetch_add(1, Ordering::SeqCst) + 1,
+        last_accessed: Instant::now(),
+        size,
+    };
+
+    let previous = shared.storage.insert(common.key, entry);
+    if let Some(previous) = previous {
+        shared.current_bytes.fetch_sub(previous.size, Ordering::SeqCst);
+    }
+    shared.current_bytes.fetch_add(size, Ordering::SeqCst);
+
+    if !common.no_reply {
+        Some(Resp::Stored)
+    } else {
+        None
+    }
+}
+
+/// Runs one parsed command against `shared` and returns the reply to send
+/// back, or `None` for a `noreply` command. Shared by every transport
+/// (TCP, WebSocket, ...) so they all see the same storage behaviour.
+pub fn dispatch(shared: &Shared, command: Req) -> Option<Resp> {
+    match command {
+        Req::Set(common) => store(shared, common),
+        Req::Get(get) => {
+            if let Some(mut entry) = shared.storage.get_mut(&get.key) {
+                if entry.expires_at.is_some_and(|e| e <= Instant::now()) {
+                    drop(entry);
+                    remove(shared, &get.key);
+                    Some(Resp::End)
+                } else {
+                    entry.last_accessed = Instant::now();
+                    Some(Resp::Value(entry.data.clone()))
+                }
+            } else {
+                Some(Resp::End)
+            }
+        }
+        Req::Gets(gets) => {
+            let values = gets
+                .keys
+                .iter()
+                .filter_map(|key| {
+                    let mut entry = shared.storage.get_mut(key)?;
+                    if entry.expires_at.is_some_and(|e| e <= Instant::now()) {
+                        drop(entry);
+                        remove(shared, key);
+                        None
+                    } else {
+                        entry.last_accessed = Instant::now();
+                        Some((entry.data.clone(), entry.cas))
+                    }
+                })
+                .collect();
+            Some(Resp::Gets(values))
+        }
+        Req::Add(common) => {
+            if shared.storage.get(&common.key).is_none() {
+                store(shared, common)
+            } else {
+                Some(Resp::NotStored)
+            }
+        }
+        Req::Replace(common) => {
+            if shared.storage.contains_key(&common.key) {
+                store(shared, common)
+            } else {
+                Some(Resp::NotStored)
+            }
+        }
+        Req::Append(mut common) => {
+            let existing = shared.storage.get(&common.key).map(|entry| entry.data.data_block.clone());
+            if let Some(existing) = existing {
+                let mut bytes = Vec::new();
+                bytes.extend_from_slice(&existing);
+                bytes.extend_from_slice(&common.data_block);
+                common.data_block = bytes;
+
+                store(shared, common)
+            } else {
+                Some(Resp::NotStored)
+            }
+        }
+        Req::Prepend(mut common) => {
+            let existing = shared.storage.get(&common.key).map(|entry| entry.data.data_block.clone());
+            if let Some(existing) = existing {
+                let mut bytes = Vec::new();
+                bytes.extend_from_slice(&common.data_block);
+                bytes.extend_from_slice(&existing);
+                common.data_block = bytes;
+
+                store(shared, common)
+            } else {
+                Some(Resp::NotStored)
+            }
+        }
+        Req::Cas(cas) => {
+            // `store` below does its own `get`/`insert` on this same key, so
+            // the cas token must be read out and the `Ref` dropped before we
+            // call it — holding the guard across `store` deadlocks against
+            // its own lookup on the same DashMap shard.
+            let current_cas = shared.storage.get(&cas.key).map(|entry| entry.cas);
+
+            let resp = match current_cas {
+                None => Resp::NotFound,
+                Some(current) if current != cas.cas_unique => Resp::Exists,
+                Some(_) => {
+                    let common = Common {
+                        name: "cas".to_string(),
+                        key: cas.key,
+                        flags: cas.flags,
+                        exptime: cas.exptime,
+                        byte_count: cas.byte_count,
+                        no_reply: cas.no_reply,
+                        data_block: cas.data_block,
+                    };
+                    store(shared, common).unwrap_or(Resp::Stored)
+                }
+            };
+
+            if cas.no_reply {
+                None
+            } else {
+                Some(resp)
+            }
+        }
+        Req::Delete(delete) => {
+            let found = remove(shared, &delete.key).is_some();
+            if delete.no_reply {
+                None
+            } else if found {
+                Some(Resp::Deleted)
+            } else {
+                Some(Resp::NotFound)
+            }
+        }
+        Req::Incr(incr_decr) => incr_or_decr(shared, incr_decr, true),
+        Req::Decr(incr_decr) => incr_or_decr(shared, incr_decr, false),
+        Req::Touch(touch) => {
+            let found = shared
+                .storage
+                .get_mut(&touch.key)
+                .map(|mut entry| {
+                    entry.expires_at = expires_at(touch.exptime);
+                })
+                .is_some();
+
+            if touch.no_reply {
+                None
+            } else if found {
+                Some(Resp::Touched)
+            } else {
+                Some(Resp::NotFound)
+            }
+        }
+        Req::FlushAll(flush_all) => {
+            shared.storage.clear();
+            shared.current_bytes.store(0, Ordering::SeqCst);
+            if flush_all.no_reply {
+                None
+            } else {
+                Some(Resp::Ok)
+            }
+        }
+        Req::Stats => {
+            let stats = vec![
+                ("pid".to_string(), std::process::id().to_string()),
+                ("curr_items".to_string(), shared.storage.len().to_string()),
+                ("bytes".to_string(), shared.current_bytes.load(Ordering::SeqCst).to_string()),
+                ("limit_maxbytes".to_string(), shared.max_bytes.to_string()),
+                ("evictions".to_string(), shared.evictions.load(Ordering::SeqCst).to_string()),
+            ];
+            Some(Resp::Stats(stats))
+        }
+    }
+}
+
+/// Applies `incr`/`decr` to the stored ASCII integer at `cmd.key`. The
+/// replacement digits can be shorter or longer than the original, so
+/// `entry.size` and `shared.current_bytes` are adjusted by the same delta
+/// the LRU cap tracks for every other mutation.
+pub fn incr_or_decr(shared: &Shared, cmd: IncrDecr, incr: bool) -> Option<Resp> {
+    let result = shared.storage.get_mut(&cmd.key).and_then(|mut entry| {
+        let current = String::from_utf8_lossy(&entry.data.data_block).parse::<u64>().ok()?;
+        let updated = if incr {
+            current.wrapping_add(cmd.value)
+        } else {
+            current.saturating_sub(cmd.value)
+        };
+
+        let new_data = updated.to_string().into_bytes();
+        let old_len = entry.data.data_block.len();
+        let new_len = new_data.len();
+
+        entry.data.data_block = new_data;
+        entry.data.byte_count = new_len;
+        entry.size = entry.size - old_len + new_len;
+
+        Some((updated, new_len as i64 - old_len as i64))
+    });
+
+    if let Some((_, delta)) = result {
+        if delta >= 0 {
+            shared.current_bytes.fetch_add(delta as usize, Ordering::SeqCst);
+        } else {
+            shared.current_bytes.fetch_sub((-delta) as usize, Ordering::SeqCst);
+        }
+    }
+
+    if cmd.no_reply {
+        None
+    } else {
+        match result {
+            Some((updated, _)) => Some(Resp::Number(updated)),
+            None => Some(Resp::NotFound),
+        }
+    }
+}
+
+#[test]
+fn test_set() {
+    let command = Common::from("set hello 0 0 5", "hello".into()).unwrap();
+
+    assert_eq!(command.name, "set");
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.flags, 0);
+    assert_eq!(command.exptime, 0);
+    assert_eq!(command.byte_count, 5);
+    assert_eq!(command.no_reply, false);
+    assert_eq!(command.data_block, "hello".as_bytes());
+}
+
+#[test]
+fn test_empty_data_set() {
+    let command = Common::from("set hello 0 0 0", Vec::new()).unwrap();
+
+    assert_eq!(command.name, "set");
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.flags, 0);
+    assert_eq!(command.exptime, 0);
+    assert_eq!(command.byte_count, 0);
+    assert_eq!(command.no_reply, false);
+    assert_eq!(command.data_block, "".as_bytes());
+}
+
+
+#[test]
+fn test_noreply_set() {
+    let command = Common::from("set hello 0 0 5 noreply", "hello".into()).unwrap();
+
+    assert_eq!(command.name, "set");
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.flags, 0);
+    assert_eq!(command.exptime, 0);
+    assert_eq!(command.byte_count, 5);
+    assert_eq!(command.no_reply, true);
+    assert_eq!(command.data_block, "hello".as_bytes());
+}
+
+#[test]
+fn test_noreply_empty_data_set() {
+    let command = Common::from("set hello 0 0 0 noreply", Vec::new()).unwrap();
+
+    assert_eq!(command.name, "set");
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.flags, 0);
+    assert_eq!(command.exptime, 0);
+    assert_eq!(command.byte_count, 0);
+    assert_eq!(command.no_reply, true);
+    assert_eq!(command.data_block, "".as_bytes());
+}
+
+#[test]
+fn test_get() {
+    let command = Get::from("get hello").unwrap();
+
+    assert_eq!(command.name, "get");
+    assert_eq!(command.key, "hello");
+}
+
+#[test]
+fn test_delete() {
+    let command = Delete::from("delete hello").unwrap();
+
+    assert_eq!(command.name, "delete");
+    assert_eq!(command.key, "hello");
+    assert!(!command.no_reply);
+}
+
+#[test]
+fn test_incr_decr() {
+    let command = IncrDecr::from("incr hello 5").unwrap();
+
+    assert_eq!(command.name, "incr");
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.value, 5);
+    assert!(!command.no_reply);
+}
+
+#[test]
+fn test_touch() {
+    let command = Touch::from("touch hello 100").unwrap();
+
+    assert_eq!(command.name, "touch");
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.exptime, 100);
+    assert!(!command.no_reply);
+}
+
+#[test]
+fn test_flush_all() {
+    let command = FlushAll::from("flush_all").unwrap();
+
+    assert!(!command.no_reply);
+}
+
+#[tokio::test]
+async fn test_read_command_set() {
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(b"set hello 0 0 5\r\nhello\r\n".to_vec()));
+    let command = read_command(&mut reader).await.ok().flatten().unwrap();
+
+    assert_eq!(command, Req::Set(Common::from("set hello 0 0 5", "hello".into()).unwrap()));
+}
+
+#[tokio::test]
+async fn test_read_command_get() {
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(b"get hello\r\n".to_vec()));
+    let command = read_command(&mut reader).await.ok().flatten().unwrap();
+
+    assert_eq!(command, Req::Get(Get::from("get hello").unwrap()));
+}
+
+#[tokio::test]
+async fn test_read_command_pipelined() {
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(b"get a\r\nget b\r\n".to_vec()));
+
+    let first = read_command(&mut reader).await.ok().flatten().unwrap();
+    let second = read_command(&mut reader).await.ok().flatten().unwrap();
+
+    assert_eq!(first, Req::Get(Get::from("get a").unwrap()));
+    assert_eq!(second, Req::Get(Get::from("get b").unwrap()));
+}
+
+#[tokio::test]
+async fn test_read_command_bad_data_chunk() {
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(b"set hello 0 0 5\r\nhelloXX".to_vec()));
+
+    assert!(matches!(read_command(&mut reader).await, Err(FrameError::BadDataChunk)));
+}
+
+#[tokio::test]
+async fn test_read_command_rejects_oversized_byte_count() {
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(b"set hello 0 0 999999999999\r\n".to_vec()));
+
+    assert!(matches!(read_command(&mut reader).await, Err(FrameError::TooLarge)));
+}
+
+#[tokio::test]
+async fn test_read_command_stays_framed_after_oversized_byte_count() {
+    let byte_count = MAX_ITEM_SIZE + 1;
+    let mut input = format!("set hello 0 0 {}\r\n", byte_count).into_bytes();
+    input.extend(std::iter::repeat_n(b'x', byte_count));
+    input.extend_from_slice(b"\r\nget world\r\n");
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(input));
+
+    assert!(matches!(read_command(&mut reader).await, Err(FrameError::TooLarge)));
+    let command = read_command(&mut reader).await.ok().flatten().unwrap();
+    assert_eq!(command, Req::Get(Get::from("get world").unwrap()));
+}
+
+#[test]
+fn test_gets() {
+    let command = Gets::from("gets hello world").unwrap();
+
+    assert_eq!(command.name, "gets");
+    assert_eq!(command.keys, vec!["hello".to_string(), "world".to_string()]);
+}
+
+#[test]
+fn test_cas() {
+    let command = Cas::from("cas hello 0 0 5 42", "hello".into()).unwrap();
+
+    assert_eq!(command.key, "hello");
+    assert_eq!(command.flags, 0);
+    assert_eq!(command.exptime, 0);
+    assert_eq!(command.byte_count, 5);
+    assert_eq!(command.cas_unique, 42);
+    assert!(!command.no_reply);
+    assert_eq!(command.data_block, "hello".as_bytes());
+}
+
+#[tokio::test]
+async fn test_read_command_cas() {
+    let mut reader = tokio::io::BufReader::new(std::io::Cursor::new(b"cas hello 0 0 5 42\r\nhello\r\n".to_vec()));
+    let command = read_command(&mut reader).await.ok().flatten().unwrap();
+
+    assert_eq!(command, Req::Cas(Cas::from("cas hello 0 0 5 42", "hello".into()).unwrap()));
+}
+
+#[test]
+fn test_dispatch_cas_stores_on_matching_token() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set hello 0 0 5", "world".into()).unwrap());
+    let cas_unique = shared.storage.get("hello").unwrap().cas;
+
+    let resp = dispatch(&shared, Req::Cas(Cas::from(&format!("cas hello 0 0 5 {}", cas_unique), "there".into()).unwrap()));
+
+    assert_eq!(resp, Some(Resp::Stored));
+    assert_eq!(shared.storage.get("hello").unwrap().data.data_block, b"there");
+}
+
+#[test]
+fn test_dispatch_cas_returns_exists_on_stale_token() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set hello 0 0 5", "world".into()).unwrap());
+
+    let resp = dispatch(&shared, Req::Cas(Cas::from("cas hello 0 0 5 999999", "there".into()).unwrap()));
+
+    assert_eq!(resp, Some(Resp::Exists));
+}
+
+#[test]
+fn test_dispatch_cas_returns_not_found_for_missing_key() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+
+    let resp = dispatch(&shared, Req::Cas(Cas::from("cas hello 0 0 5 1", "there".into()).unwrap()));
+
+    assert_eq!(resp, Some(Resp::NotFound));
+}
+
+#[test]
+fn test_dispatch_delete_removes_existing_key() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set hello 0 0 5", "world".into()).unwrap());
+
+    let resp = dispatch(&shared, Req::Delete(Delete::from("delete hello").unwrap()));
+
+    assert_eq!(resp, Some(Resp::Deleted));
+    assert!(!shared.storage.contains_key("hello"));
+}
+
+#[test]
+fn test_dispatch_delete_returns_not_found_for_missing_key() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+
+    let resp = dispatch(&shared, Req::Delete(Delete::from("delete hello").unwrap()));
+
+    assert_eq!(resp, Some(Resp::NotFound));
+}
+
+#[test]
+fn test_dispatch_incr_adds_to_stored_number() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set counter 0 0 1", "5".into()).unwrap());
+
+    let resp = dispatch(&shared, Req::Incr(IncrDecr::from("incr counter 3").unwrap()));
+
+    assert_eq!(resp, Some(Resp::Number(8)));
+}
+
+#[test]
+fn test_dispatch_incr_keeps_byte_accounting_in_sync_when_value_grows() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set counter 0 0 1", "9".into()).unwrap());
+
+    dispatch(&shared, Req::Incr(IncrDecr::from("incr counter 999999999").unwrap()));
+
+    let entry = shared.storage.get("counter").unwrap();
+    let expected_bytes = "counter".len() + entry.data.data_block.len();
+    assert_eq!(entry.size, expected_bytes);
+    assert_eq!(shared.current_bytes.load(Ordering::SeqCst), expected_bytes);
+}
+
+#[test]
+fn test_dispatch_touch_updates_expiry() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set hello 0 0 5", "world".into()).unwrap());
+
+    let resp = dispatch(&shared, Req::Touch(Touch::from("touch hello 100").unwrap()));
+
+    assert_eq!(resp, Some(Resp::Touched));
+    assert!(shared.storage.get("hello").unwrap().expires_at.is_some());
+}
+
+#[test]
+fn test_dispatch_flush_all_clears_storage() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+    store(&shared, Common::from("set hello 0 0 5", "world".into()).unwrap());
+
+    let resp = dispatch(&shared, Req::FlushAll(FlushAll::from("flush_all").unwrap()));
+
+    assert_eq!(resp, Some(Resp::Ok));
+    assert!(shared.storage.is_empty());
+    assert_eq!(shared.current_bytes.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_get_from_rejects_missing_key() {
+    assert!(Get::from("get").is_err());
+}
+
+#[test]
+fn test_store_evicts_lru_when_over_cap() {
+    let shared = Shared::new(10);
+
+    store(&shared, Common::from("set a 0 60 5", "aaaaa".into()).unwrap());
+    store(&shared, Common::from("set b 0 60 5", "bbbbb".into()).unwrap());
+
+    assert!(!shared.storage.contains_key("a"));
+    assert!(shared.storage.contains_key("b"));
+    assert_eq!(shared.evictions.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_store_keeps_recently_accessed_entry() {
+    let shared = Shared::new(13);
+
+    store(&shared, Common::from("set a 0 60 5", "aaaaa".into()).unwrap());
+    store(&shared, Common::from("set b 0 60 5", "bbbbb".into()).unwrap());
+    shared.storage.get_mut("a").unwrap().last_accessed = Instant::now();
+    store(&shared, Common::from("set c 0 60 5", "ccccc".into()).unwrap());
+
+    assert!(shared.storage.contains_key("a"));
+    assert!(!shared.storage.contains_key("b"));
+    assert!(shared.storage.contains_key("c"));
+    assert_eq!(shared.evictions.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_store_persists_forever_when_exptime_zero() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+
+    store(&shared, Common::from("set hello 0 0 5", "hello".into()).unwrap());
+
+    let entry = shared.storage.get("hello").unwrap();
+    assert_eq!(entry.expires_at, None);
+}
+
+#[test]
+fn test_expires_at_treats_large_exptime_as_absolute_timestamp() {
+    let now_unix = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+    assert!(expires_at(now_unix.saturating_sub(1)).is_some_and(|e| e <= Instant::now()));
+    assert!(expires_at(now_unix + 60).is_some());
+}
+
+#[test]
+fn test_expires_at_treats_negative_exptime_as_already_expired() {
+    assert!(expires_at(-1).is_some_and(|e| e <= Instant::now()));
+}
+
+#[test]
+fn test_common_from_parses_negative_exptime() {
+    let command = Common::from("set hello 0 -1 5", "hello".into()).unwrap();
+
+    assert_eq!(command.exptime, -1);
+}
+
+#[test]
+fn test_reap_expired_removes_only_expired_entries() {
+    let shared = Shared::new(DEFAULT_MAX_BYTES);
+
+    store(&shared, Common::from("set stale 0 1 5", "aaaaa".into()).unwrap());
+    store(&shared, Common::from("set fresh 0 0 5", "bbbbb".into()).unwrap());
+    shared.storage.get_mut("stale").unwrap().expires_at = Some(Instant::now());
+
+    let reaped = reap_expired(&shared);
+
+    assert_eq!(reaped, 1);
+    assert!(!shared.storage.contains_key("stale"));
+    assert!(shared.storage.contains_key("fresh"));
+}
@@ -0,0 +1,109 @@
+//! Optional WebSocket frontend, enabled with the `websocket` feature. Each
+//! WS message carries one command line (plus data block for storage ops),
+//! gets framed through the same [`read_command`] parser the TCP listener
+//! uses, and is handled by the shared [`dispatch`] so both transports see
+//! the same storage.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::BufReader;
+use tokio::net::TcpListener;
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{dispatch, read_command, FrameError, Req, Resp, Shared};
+
+/// Binds `addr` and serves WebSocket connections until the listener errors,
+/// dispatching every command against `shared` the same way the TCP
+/// listener in `main` does.
+pub async fn serve(shared: Arc<Shared>, addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            let Ok(ws_stream) = accept_async(socket).await else {
+                return;
+            };
+            let (mut write, mut read) = ws_stream.split();
+
+            while let Some(Ok(message)) = read.next().await {
+                let bytes = match message {
+                    Message::Text(text) => text.into_bytes(),
+                    Message::Binary(data) => data,
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+
+                let command = match parse_command(bytes).await {
+                    Ok(None) => break,
+                    Ok(Some(command)) => command,
+                    Err(FrameError::BadDataChunk) => {
+                        let reply = Resp::ClientError("bad data chunk".to_string()).to_string();
+                        if write.send(Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    Err(FrameError::TooLarge) => {
+                        let reply = Resp::ServerError("object too large for cache".to_string()).to_string();
+                        if write.send(Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    Err(FrameError::Protocol(_)) => continue,
+                };
+
+                if let Some(resp) = dispatch(&shared, command)
+                    && write.send(Message::Text(resp.to_string())).await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Parses one command out of a WS message's raw bytes, reusing the same
+/// framing [`read_command`] applies to TCP connections. Split out of
+/// [`serve`] so the parsing path can be unit tested without a real socket.
+async fn parse_command(bytes: Vec<u8>) -> Result<Option<Req>, FrameError> {
+    let mut reader = BufReader::new(std::io::Cursor::new(bytes));
+    read_command(&mut reader).await
+}
+
+#[tokio::test]
+async fn test_parse_command_set() {
+    let command = parse_command(b"set hello 0 0 5\r\nhello\r\n".to_vec()).await.ok().flatten().unwrap();
+
+    assert_eq!(command, Req::Set(crate::Common::from("set hello 0 0 5", "hello".into()).unwrap()));
+}
+
+#[tokio::test]
+async fn test_parse_command_bad_data_chunk() {
+    let result = parse_command(b"set hello 0 0 5\r\nhelloXX".to_vec()).await;
+
+    assert!(matches!(result, Err(FrameError::BadDataChunk)));
+}
+
+#[tokio::test]
+async fn test_parse_command_rejects_oversized_byte_count() {
+    let result = parse_command(b"set hello 0 0 999999999999\r\n".to_vec()).await;
+
+    assert!(matches!(result, Err(FrameError::TooLarge)));
+}
+
+#[tokio::test]
+async fn test_parse_command_then_dispatch_stores_value() {
+    let shared = Shared::new(crate::DEFAULT_MAX_BYTES);
+    let command = parse_command(b"set hello 0 0 5\r\nworld\r\n".to_vec()).await.ok().flatten().unwrap();
+
+    let resp = dispatch(&shared, command);
+
+    assert_eq!(resp, Some(Resp::Stored));
+    assert_eq!(shared.storage.get("hello").unwrap().data.data_block, b"world");
+}